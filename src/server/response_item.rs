@@ -1,5 +1,11 @@
 use lazy_static::lazy_static;
-use lsp_types::{CompletionItemKind, Range, SymbolKind, Url};
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, CompletionItem,
+    CompletionItemKind, CompletionTextEdit, Diagnostic, DiagnosticSeverity, Documentation,
+    InlayHint, InlayHintKind, InlayHintLabel, InsertTextFormat, ParameterInformation,
+    ParameterLabel, Position, Range, SignatureHelp, SignatureInformation, SymbolKind, TextEdit,
+    Url,
+};
 use regex::Regex;
 use tree_sitter::Node;
 
@@ -63,6 +69,151 @@ impl Param {
     }
 }
 
+fn active_parameter(
+    code: &str,
+    args_node: &Node,
+    params: &[Param],
+    ignore_name: bool,
+    byte: usize,
+) -> Option<u32> {
+    if params.is_empty() {
+        return None;
+    }
+
+    let mut positional = 0u32;
+    for arg in args_node.named_children(&mut args_node.walk()) {
+        if !ignore_name && arg.kind() == "assignment" {
+            if let Some(left) = arg.child_by_field_name("left") {
+                let name = node_text(code, &left);
+                if let Some(idx) = params.iter().position(|p| p.name == name) {
+                    if byte <= arg.end_byte() {
+                        return Some(idx as u32);
+                    }
+                    continue;
+                }
+            }
+        }
+        if byte <= arg.end_byte() {
+            return Some(positional.min(params.len() as u32 - 1));
+        }
+        positional += 1;
+    }
+
+    Some(positional.min(params.len() as u32 - 1))
+}
+
+fn escape_snippet_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '$' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn builtin_doc_url(name: &str) -> Option<&'static str> {
+    lazy_static! {
+        static ref BUILTIN_DOC_URLS: std::collections::HashMap<&'static str, &'static str> = {
+            let mut m = std::collections::HashMap::new();
+            m.insert(
+                "cube",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Primitive_Solids#cube",
+            );
+            m.insert(
+                "sphere",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Primitive_Solids#sphere",
+            );
+            m.insert(
+                "cylinder",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Primitive_Solids#cylinder",
+            );
+            m.insert(
+                "polygon",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/2D_Primitives#polygon",
+            );
+            m.insert(
+                "text",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Text#text",
+            );
+            m.insert(
+                "translate",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Transformations#translate",
+            );
+            m.insert(
+                "rotate",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Transformations#rotate",
+            );
+            m.insert(
+                "scale",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Transformations#scale",
+            );
+            m.insert(
+                "mirror",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Transformations#mirror",
+            );
+            m.insert(
+                "color",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Transformations#color",
+            );
+            m.insert(
+                "union",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/CSG_Modelling#union",
+            );
+            m.insert(
+                "difference",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/CSG_Modelling#difference",
+            );
+            m.insert(
+                "intersection",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/CSG_Modelling#intersection",
+            );
+            m.insert(
+                "hull",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/The_OpenSCAD_Language#hull",
+            );
+            m.insert(
+                "minkowski",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/The_OpenSCAD_Language#minkowski",
+            );
+            m.insert(
+                "linear_extrude",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Using_the_2D_Subsystem#linear_extrude",
+            );
+            m.insert(
+                "rotate_extrude",
+                "https://en.wikibooks.org/wiki/OpenSCAD_User_Manual/Using_the_2D_Subsystem#rotate_extrude",
+            );
+            m
+        };
+    };
+
+    BUILTIN_DOC_URLS.get(name).copied()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut prev = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cur = row[j];
+            row[j] = if a[i - 1] == b[j - 1] {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j - 1])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
 #[derive(Default)]
 pub(crate) enum ItemKind {
     #[default]
@@ -150,6 +301,262 @@ impl Item {
         snippet
     }
 
+    pub(crate) fn make_postfix_completion_item(
+        &self,
+        captured_expr: &str,
+        trigger_range: Range,
+        args: &Cli,
+    ) -> Option<CompletionItem> {
+        let (params, flags) = match &self.kind {
+            ItemKind::Module { params, flags } => (params, *flags),
+            _ => return None,
+        };
+
+        if BuiltinFlags::IS_OPREATOR & flags == 0 {
+            return None;
+        }
+
+        let ignore_name = BuiltinFlags::IGNORE_PARAM_NAME & flags != 0;
+        let snippet_params = Param::make_snippet(params, ignore_name, args);
+        let escaped_expr = escape_snippet_text(captured_expr);
+        let snippet = format!("{}({}) {} $0", self.name, snippet_params, escaped_expr);
+
+        Some(CompletionItem {
+            label: format!(".{}", self.name),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(self.make_label()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: trigger_range,
+                new_text: snippet,
+            })),
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn make_signature_information(&self) -> Option<SignatureInformation> {
+        let params = match &self.kind {
+            ItemKind::Function { params, .. } => params,
+            ItemKind::Module { params, .. } => params,
+            _ => return None,
+        };
+
+        let label = self.make_label();
+        let mut search_from = 0usize;
+        let parameters = params
+            .iter()
+            .filter_map(|p| {
+                let rendered = match &p.default {
+                    Some(d) => format!("{}={}", p.name, d),
+                    None => p.name.clone(),
+                };
+                let start = label[search_from..].find(&rendered)? + search_from;
+                let end = start + rendered.len();
+                search_from = end;
+                // ParameterLabel::LabelOffsets are UTF-16 code units, not bytes.
+                let start_utf16 = label[..start].encode_utf16().count() as u32;
+                let end_utf16 = label[..end].encode_utf16().count() as u32;
+                Some(ParameterInformation {
+                    label: ParameterLabel::LabelOffsets([start_utf16, end_utf16]),
+                    documentation: None,
+                })
+            })
+            .collect();
+
+        Some(SignatureInformation {
+            label,
+            documentation: self.doc.clone().map(Documentation::String),
+            parameters: Some(parameters),
+            active_parameter: None,
+        })
+    }
+
+    pub(crate) fn make_signature_help(
+        &self,
+        code: &str,
+        args_node: &Node,
+        byte: usize,
+    ) -> Option<SignatureHelp> {
+        let (params, flags) = match &self.kind {
+            ItemKind::Function { flags, params } => (params, *flags),
+            ItemKind::Module { flags, params } => (params, *flags),
+            _ => return None,
+        };
+
+        let signature = self.make_signature_information()?;
+        let ignore_name = BuiltinFlags::IGNORE_PARAM_NAME & flags != 0;
+        let active_parameter = active_parameter(code, args_node, params, ignore_name, byte);
+
+        Some(SignatureHelp {
+            signatures: vec![signature],
+            active_signature: Some(0),
+            active_parameter,
+        })
+    }
+
+    pub(crate) fn make_inlay_hints(
+        &self,
+        args_node: &Node,
+        ignore_param_hints: bool,
+    ) -> Vec<InlayHint> {
+        if ignore_param_hints {
+            return vec![];
+        }
+
+        let (params, flags) = match &self.kind {
+            ItemKind::Function { flags, params } => (params, *flags),
+            ItemKind::Module { flags, params } => (params, *flags),
+            _ => return vec![],
+        };
+
+        if BuiltinFlags::IGNORE_PARAM_NAME & flags != 0 {
+            return vec![];
+        }
+
+        let mut hints = Vec::new();
+        let mut param_iter = params.iter();
+        for arg in args_node.named_children(&mut args_node.walk()) {
+            if arg.kind() == "assignment" {
+                continue;
+            }
+            let Some(param) = param_iter.next() else {
+                break;
+            };
+            let pos = arg.start_position();
+            hints.push(InlayHint {
+                position: Position {
+                    line: pos.row as u32,
+                    character: pos.column as u32,
+                },
+                label: InlayHintLabel::String(format!("{}:", param.name)),
+                kind: Some(InlayHintKind::PARAMETER),
+                text_edits: None,
+                tooltip: None,
+                padding_left: Some(false),
+                padding_right: Some(true),
+                data: None,
+            });
+        }
+        hints
+    }
+
+    pub(crate) fn make_call_hierarchy_item(
+        &self,
+        uri: Url,
+        name_node: &Node,
+    ) -> Option<CallHierarchyItem> {
+        match self.kind {
+            ItemKind::Function { .. } | ItemKind::Module { .. } => Some(CallHierarchyItem {
+                name: self.name.clone(),
+                kind: self.get_symbol_kind(),
+                tags: None,
+                detail: Some(self.make_label()),
+                uri,
+                range: self.range,
+                selection_range: name_node.lsp_range(),
+                data: None,
+            }),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn make_incoming_call(
+        &self,
+        uri: Url,
+        name_node: &Node,
+        from_ranges: Vec<Range>,
+    ) -> Option<CallHierarchyIncomingCall> {
+        Some(CallHierarchyIncomingCall {
+            from: self.make_call_hierarchy_item(uri, name_node)?,
+            from_ranges,
+        })
+    }
+
+    pub(crate) fn make_outgoing_call(
+        &self,
+        uri: Url,
+        name_node: &Node,
+        from_ranges: Vec<Range>,
+    ) -> Option<CallHierarchyOutgoingCall> {
+        Some(CallHierarchyOutgoingCall {
+            to: self.make_call_hierarchy_item(uri, name_node)?,
+            from_ranges,
+        })
+    }
+
+    pub(crate) fn check_call_diagnostics(&self, code: &str, args_node: &Node) -> Vec<Diagnostic> {
+        if self.is_builtin {
+            return vec![];
+        }
+
+        let (params, flags) = match &self.kind {
+            ItemKind::Function { flags, params } => (params, *flags),
+            ItemKind::Module { flags, params } => (params, *flags),
+            _ => return vec![],
+        };
+
+        if BuiltinFlags::IGNORE_PARAM_NAME & flags != 0 {
+            return vec![];
+        }
+
+        let mut diagnostics = vec![];
+        let mut supplied = vec![false; params.len()];
+        let mut positional_count = 0usize;
+
+        for arg in args_node.named_children(&mut args_node.walk()) {
+            if arg.kind() == "assignment" {
+                let Some(left) = arg.child_by_field_name("left") else {
+                    continue;
+                };
+                let name = node_text(code, &left);
+                match params.iter().position(|p| p.name == name) {
+                    Some(idx) => supplied[idx] = true,
+                    None => {
+                        let suggestion = params.iter().min_by_key(|p| edit_distance(&p.name, name));
+                        let message = match suggestion {
+                            Some(p) => {
+                                format!("unknown parameter `{}`, did you mean `{}`?", name, p.name)
+                            }
+                            None => format!("unknown parameter `{}`", name),
+                        };
+                        diagnostics.push(Diagnostic {
+                            range: left.lsp_range(),
+                            severity: Some(DiagnosticSeverity::ERROR),
+                            message,
+                            ..Default::default()
+                        });
+                    }
+                }
+                continue;
+            }
+
+            if positional_count < params.len() {
+                supplied[positional_count] = true;
+            } else {
+                diagnostics.push(Diagnostic {
+                    range: arg.lsp_range(),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: format!("too many arguments passed to `{}`", self.name),
+                    ..Default::default()
+                });
+            }
+            positional_count += 1;
+        }
+
+        for (idx, param) in params.iter().enumerate() {
+            if param.default.is_none() && !supplied[idx] {
+                diagnostics.push(Diagnostic {
+                    range: args_node.lsp_range(),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: format!("missing required parameter `{}`", param.name),
+                    ..Default::default()
+                });
+            }
+        }
+
+        diagnostics
+    }
+
     pub(crate) fn make_hover(&self) -> String {
         let mut label = match &self.label {
             Some(label) => label.to_owned(),
@@ -160,6 +567,26 @@ impl Item {
             ItemKind::Module { .. } => format!("```scad\nmodule {}\n```", label),
             _ => format!("```scad\n{}\n```", label),
         };
+
+        let params = match &self.kind {
+            ItemKind::Function { params, .. } => Some(params),
+            ItemKind::Module { params, .. } => Some(params),
+            _ => None,
+        };
+        if let Some(params) = params {
+            if !params.is_empty() {
+                let rows = params
+                    .iter()
+                    .map(|p| match &p.default {
+                        Some(d) => format!("- `{}` (default: `{}`)", p.name, d),
+                        None => format!("- `{}`", p.name),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                label = format!("{}\n\n**Parameters**\n\n{}\n", label, rows);
+            }
+        }
+
         if let Some(doc) = &self.doc {
             if self.is_builtin {
                 label = format!("{}\n---\n\n{}\n", label, doc);
@@ -167,6 +594,13 @@ impl Item {
                 label = format!("{}\n---\n\n<pre>\n{}\n</pre>\n", label, doc);
             }
         }
+
+        if self.is_builtin {
+            if let Some(url) = builtin_doc_url(&self.name) {
+                label = format!("{}\n[Documentation]({})\n", label, url);
+            }
+        }
+
         // print!("{}", &label);
         label
     }